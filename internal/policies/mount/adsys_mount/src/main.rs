@@ -1,12 +1,15 @@
 use clap::Parser;
 use gio::{
     self,
-    traits::{FileExt, MountOperationExt},
+    traits::{FileExt, MountExt, MountOperationExt},
 };
 use glib::ObjectExt;
 use log::{debug, error, warn};
 use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
     fs,
+    rc::Rc,
     sync::{Arc, Mutex},
 };
 
@@ -16,25 +19,86 @@ use logger::Logger;
 mod error; // Includes our error implementation from the error.rs file;
 use error::AdsysMountError;
 
+/// The format used to report the outcome of the run on stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// Only log the outcome, as this binary has always done.
+    Text,
+    /// Additionally print a machine-readable JSON report once every entry has been processed.
+    Json,
+}
+
+/// The operation to perform against every entry in the mounts file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Action {
+    /// Mount every entry listed in the mounts file (the default).
+    Mount,
+    /// Unmount every entry listed in the mounts file.
+    Unmount,
+    /// Eject every entry listed in the mounts file.
+    Eject,
+}
+
 /// Arguments required to run this binary
 #[derive(Debug, clap::Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
     /// Path for the file containing the mounts for the user.
     mounts_file: String,
+
+    /// Whether to mount, unmount or eject the entries listed in the mounts file.
+    #[arg(long, value_enum, default_value = "mount")]
+    action: Action,
+
+    /// Path for a file mapping URI prefixes to the credentials to use for them.
+    #[arg(long)]
+    credentials_file: Option<String>,
+
+    /// Report format to print on stdout once every entry has been processed.
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Maximum number of mounts to have in flight at once.
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(usize).range(1..))]
+    max_parallel: usize,
+
+    /// Default number of times to retry a transiently failed mount, unless overridden by the
+    /// entry's own `retries` option.
+    #[arg(long, default_value_t = 0)]
+    max_retries: u32,
+
+    /// After the initial mount pass, keep running and re-mount any entry whose mount
+    /// disappears (e.g. a flaky network share dropping on suspend/resume).
+    #[arg(long)]
+    monitor: bool,
 }
 
 /// Represents a mount point read from the mounts file.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct MountEntry {
     mount_path: String,
     is_anonymous: bool,
+    options: MountOptions,
+}
+
+/// Per-entry mount options, parsed from the comma-separated list trailing a mounts file line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct MountOptions {
+    /// Cancel the mount attempt if it hasn't completed after this many seconds.
+    timeout_secs: Option<u32>,
+    /// How many times to retry the mount on a transient failure; 0 leaves it to `--max-retries`.
+    retries: u32,
+    /// Whether the share should be recorded as read-only.
+    readonly: bool,
+    /// Forces a credentialed mount even if the entry is prefixed with `[anonymous]`.
+    skip_anonymous: bool,
 }
 
 /// Struct representing the message that is to be passed in the glib channel.
 struct Msg {
     path: String,
     status: MountStatus,
+    readonly: bool,
 }
 
 /// Represents the status returned by a mount attempt.
@@ -45,6 +109,86 @@ enum MountStatus {
     Error(glib::Error),
 }
 
+/// One record of the machine-readable mount report, mirroring a single `Msg`.
+#[derive(Debug, serde::Serialize)]
+struct MountReportEntry {
+    uri: String,
+    status: &'static str,
+    readonly: bool,
+    error: Option<MountReportError>,
+}
+
+/// The gio error domain, code and message for a failed entry in the mount report.
+#[derive(Debug, serde::Serialize)]
+struct MountReportError {
+    domain: String,
+    code: i32,
+    message: String,
+}
+
+impl MountReportEntry {
+    fn from_msg(msg: &Msg) -> Self {
+        match &msg.status {
+            MountStatus::Done => MountReportEntry {
+                uri: msg.path.clone(),
+                status: "done",
+                readonly: msg.readonly,
+                error: None,
+            },
+            MountStatus::Error(e) if e.matches(gio::IOErrorEnum::AlreadyMounted) => {
+                MountReportEntry {
+                    uri: msg.path.clone(),
+                    status: "already-mounted",
+                    readonly: msg.readonly,
+                    error: None,
+                }
+            }
+            MountStatus::Error(e) if e.matches(gio::IOErrorEnum::NotMounted) => MountReportEntry {
+                uri: msg.path.clone(),
+                status: "not-mounted",
+                readonly: msg.readonly,
+                error: None,
+            },
+            MountStatus::Error(e) => MountReportEntry {
+                uri: msg.path.clone(),
+                status: "error",
+                readonly: msg.readonly,
+                error: Some(MountReportError {
+                    domain: e.domain().to_string(),
+                    code: e.code(),
+                    message: e.message().to_string(),
+                }),
+            },
+            MountStatus::Asked => MountReportEntry {
+                uri: msg.path.clone(),
+                status: "asked",
+                readonly: msg.readonly,
+                error: None,
+            },
+        }
+    }
+}
+
+/// Where to read a credential's secret value from.
+#[derive(Debug, Clone)]
+enum SecretRef {
+    /// The value is given inline in the credentials file.
+    Inline(String),
+    /// The value should be read from the named environment variable.
+    EnvVar(String),
+    /// The value should be read from the given file path.
+    File(String),
+}
+
+/// Credentials to use for every mount whose URI starts with `uri_prefix`.
+#[derive(Debug, Clone)]
+struct Credential {
+    uri_prefix: String,
+    username: Option<String>,
+    domain: Option<String>,
+    password: Option<SecretRef>,
+}
+
 fn main() -> Result<(), AdsysMountError> {
     let args = Args::parse();
 
@@ -53,7 +197,10 @@ fn main() -> Result<(), AdsysMountError> {
         log::set_max_level(log::LevelFilter::Debug);
     }
 
-    debug!("Mounting entries listed in {}", args.mounts_file);
+    debug!(
+        "Running action {:?} on entries listed in {}",
+        args.action, args.mounts_file
+    );
 
     let parsed_entries = match parse_entries(&args.mounts_file) {
         Ok(v) => v,
@@ -63,6 +210,17 @@ fn main() -> Result<(), AdsysMountError> {
         }
     };
 
+    let credentials: Arc<Vec<Credential>> = match &args.credentials_file {
+        Some(path) => match parse_credentials(path) {
+            Ok(v) => Arc::new(v),
+            Err(e) => {
+                error!("Error when parsing credentials file: {}", e);
+                return Err(AdsysMountError::ParseError);
+            }
+        },
+        None => Arc::new(Vec::new()),
+    };
+
     // Setting up the channel used for communication between the mount operations and the main function.
     let g_ctx = glib::MainContext::default();
     let (tx, rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
@@ -70,8 +228,43 @@ fn main() -> Result<(), AdsysMountError> {
     // Grabs the ammount of mounts to be done before passing the ownership of parsed_entries.
     let mut mounts_left = parsed_entries.len();
 
-    for entry in parsed_entries {
-        handle_mount(entry, tx.clone());
+    // Keeps track of the gio::Files that were successfully mounted, keyed by URI, so that an
+    // interrupted session can still tear them down cleanly. A fresh successful (re-)mount
+    // replaces rather than duplicates the entry for its URI, and `--monitor` prunes it when the
+    // mount disappears, so this never grows unbounded over a long-running session. Only ever
+    // populated in `Action::Mount`.
+    let mounted_files: Arc<Mutex<HashMap<String, gio::File>>> = Arc::new(Mutex::new(HashMap::new()));
+
+    // Kept alive for the lifetime of the process so the `mount-removed` handler it carries
+    // doesn't get disconnected; only ever set in `--monitor` mode.
+    let mut volume_monitor_guard: Option<gio::VolumeMonitor> = None;
+
+    match args.action {
+        Action::Mount => {
+            let mount_uris: HashSet<String> =
+                parsed_entries.iter().map(|e| e.mount_path.clone()).collect();
+
+            let scheduler = Scheduler::new(
+                parsed_entries,
+                args.max_retries,
+                args.max_parallel,
+                g_ctx.clone(),
+                tx.clone(),
+                Arc::clone(&mounted_files),
+                Arc::clone(&credentials),
+            );
+            Scheduler::fill(&scheduler);
+
+            if args.monitor {
+                volume_monitor_guard =
+                    Some(install_monitor(scheduler, mount_uris, Arc::clone(&mounted_files)));
+            }
+        }
+        Action::Unmount | Action::Eject => {
+            for entry in parsed_entries {
+                handle_unmount(args.action, entry, tx.clone());
+            }
+        }
     }
 
     // Sets the main loop glib to be used by the mounts
@@ -83,20 +276,22 @@ fn main() -> Result<(), AdsysMountError> {
     // Clones the variables that are going to be moved into the closure.
     let g_loop_clone = g_loop.clone();
     let mu_clone = Arc::clone(&mu);
+    let monitor = args.monitor;
 
     // Attaches the receiver to the main context, along with a closure that is called everytime there is a new message in the channel.
     rx.attach(Some(&g_ctx), move |x| {
         match x.status {
-            MountStatus::Done => debug!("Mounting of {} was successful", x.path),
-            MountStatus::Error(_) => {
-                warn!("Failed when mounting {}", x.path);
-                mu_clone.lock().unwrap().push(x);
-            }
+            MountStatus::Done => debug!("Operation on {} was successful", x.path),
+            MountStatus::Error(_) => warn!("Failed when operating on {}", x.path),
             _ => {}
         };
-        mounts_left -= 1;
+        // Every message is kept, not just failures, so the final report can cover every entry.
+        mu_clone.lock().unwrap().push(x);
+        mounts_left = mounts_left.saturating_sub(1);
+        // In `--monitor` mode the loop keeps running after every entry settles, watching for
+        // mounts to re-attempt, so it's only ever quit by the signal handler below.
         glib::Continue(match mounts_left {
-            0 => {
+            0 if !monitor => {
                 // Ends the main loop if there are no more mounts left.
                 g_loop_clone.quit();
                 false
@@ -105,20 +300,83 @@ fn main() -> Result<(), AdsysMountError> {
         })
     });
 
+    // In mount mode, guarantee that whatever got mounted this run is torn down again if the
+    // process is interrupted, instead of leaving dangling GVFS mounts behind.
+    if args.action == Action::Mount {
+        // Teardown requests must run on the same MainContext the mounts were created on, so the
+        // signal handler only notifies this channel instead of calling gio directly.
+        let (tx_shutdown, rx_shutdown) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+
+        let tx_sigint = tx_shutdown.clone();
+        glib::source::unix_signal_add(libc::SIGINT, move || {
+            let _ = tx_sigint.send(());
+            glib::Continue(false)
+        });
+
+        let tx_sigterm = tx_shutdown.clone();
+        glib::source::unix_signal_add(libc::SIGTERM, move || {
+            let _ = tx_sigterm.send(());
+            glib::Continue(false)
+        });
+        drop(tx_shutdown);
+
+        let g_loop_shutdown = g_loop.clone();
+        let mounted_files_shutdown = Arc::clone(&mounted_files);
+        rx_shutdown.attach(Some(&g_ctx), move |_| {
+            debug!("Termination requested, unmounting entries mounted this session");
+            let files: Vec<gio::File> = mounted_files_shutdown.lock().unwrap().drain().map(|(_, f)| f).collect();
+            if files.is_empty() {
+                g_loop_shutdown.quit();
+            }
+            // The loop is only quit once every unmount has completed.
+            let pending = Rc::new(Cell::new(files.len()));
+            for f in files {
+                let pending = Rc::clone(&pending);
+                let g_loop_unmounted = g_loop_shutdown.clone();
+                f.unmount_mountable_with_operation(
+                    gio::MountUnmountFlags::NONE,
+                    gio::MountOperation::NONE,
+                    gio::Cancellable::NONE,
+                    move |r| {
+                        if let Err(e) = r {
+                            // Ensures that we don't report an error if the mount was already gone.
+                            if !e.matches(gio::IOErrorEnum::NotMounted) {
+                                warn!("Failed to unmount during shutdown: {}", e);
+                            }
+                        }
+                        pending.set(pending.get() - 1);
+                        if pending.get() == 0 {
+                            g_loop_unmounted.quit();
+                        }
+                    },
+                );
+            }
+            glib::Continue(false)
+        });
+    }
+
     g_loop.run();
 
-    // Evaluates the arc content to check if at least one operation failed.
+    let messages = mu.lock().unwrap();
+
+    if args.format == Format::Json {
+        let report: Vec<MountReportEntry> = messages.iter().map(MountReportEntry::from_msg).collect();
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => error!("Failed to serialize mount report: {}", e),
+        }
+    }
+
+    // Evaluates the accumulated messages to check if at least one operation failed.
     let mut had_error = false;
-    let errors = mu.lock().unwrap();
-    if errors.len() != 0 {
-        for err in errors.iter() {
-            if let MountStatus::Error(e) = &err.status {
-                warn!("Mount process for {} failed: {}", err.path, e);
-
-                // Ensures that the function will not error out if the location was already mounted.
-                if !e.matches(gio::IOErrorEnum::AlreadyMounted) {
-                    had_error = true;
-                }
+    for msg in messages.iter() {
+        if let MountStatus::Error(e) = &msg.status {
+            warn!("Operation for {} failed: {}", msg.path, e);
+
+            // Ensures that the function will not error out if the location was already mounted
+            // or, symmetrically, already unmounted.
+            if !e.matches(gio::IOErrorEnum::AlreadyMounted) && !e.matches(gio::IOErrorEnum::NotMounted) {
+                had_error = true;
             }
         }
     }
@@ -143,46 +401,414 @@ fn parse_entries(path: &String) -> Result<Vec<MountEntry>, std::io::Error> {
             continue;
         }
 
-        parsed_entries.push(match p.strip_prefix("[anonymous]") {
-            Some(s) => MountEntry {
-                mount_path: s.to_string(),
-                is_anonymous: true,
+        // The URI and its options are separated by a tab, mirroring how the options list is
+        // appended in e.g. `smb://host/share\tro,timeout=30,retries=3`.
+        let (uri_part, options_part) = match p.split_once('\t') {
+            Some((uri, options)) => (uri, options),
+            None => (p, ""),
+        };
+
+        let (mount_path, is_anonymous) = match uri_part.strip_prefix("[anonymous]") {
+            Some(s) => (s.to_string(), true),
+            None => (uri_part.to_string(), false),
+        };
+
+        parsed_entries.push(MountEntry {
+            mount_path,
+            is_anonymous,
+            options: parse_mount_options(options_part),
+        });
+    }
+
+    Ok(parsed_entries)
+}
+
+/// Parses a comma-separated mount option list. Options this binary doesn't act on are logged
+/// and ignored, the same way bcachefs-tools' mount option parsing handles unknown keys.
+fn parse_mount_options(s: &str) -> MountOptions {
+    let mut options = MountOptions::default();
+
+    for opt in s.split(',') {
+        let opt = opt.trim();
+        if opt.is_empty() {
+            continue;
+        }
+
+        match opt.split_once('=') {
+            Some(("timeout", v)) => match v.parse() {
+                Ok(secs) => options.timeout_secs = Some(secs),
+                Err(_) => warn!("Ignoring invalid timeout value: {}", v),
             },
-            None => MountEntry {
-                mount_path: p.to_string(),
-                is_anonymous: false,
+            Some(("retries", v)) => match v.parse() {
+                Ok(retries) => options.retries = retries,
+                Err(_) => warn!("Ignoring invalid retries value: {}", v),
             },
+            Some((key, _)) => debug!("Ignoring unknown mount option: {}", key),
+            None => match opt {
+                "ro" | "readonly" => options.readonly = true,
+                "skip-anonymous" => options.skip_anonymous = true,
+                _ => debug!("Ignoring unknown mount option: {}", opt),
+            },
+        }
+    }
+
+    options
+}
+
+/// Reads the credentials file and parses the per-URI-prefix credentials listed in it.
+///
+/// Each line has the form `uri_prefix username domain password_ref`, where `username` and
+/// `domain` may be `-` if unset, and `password_ref` is either an inline value, `env:NAME` to
+/// read it from an environment variable, or `file:PATH` to read it from a file.
+fn parse_credentials(path: &String) -> Result<Vec<Credential>, std::io::Error> {
+    debug!("Parsing credentials file content");
+
+    let mut credentials: Vec<Credential> = Vec::new();
+
+    let content = fs::read_to_string(path)?;
+
+    for line in content.split_terminator('\n') {
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 4 {
+            warn!("Ignoring malformed line in credentials file: {}", line);
+            continue;
+        }
+
+        let to_option = |s: &str| (s != "-").then(|| s.to_string());
+
+        credentials.push(Credential {
+            uri_prefix: fields[0].to_string(),
+            username: to_option(fields[1]),
+            domain: to_option(fields[2]),
+            password: to_option(fields[3]).map(|s| match s.split_once(':') {
+                Some(("env", name)) => SecretRef::EnvVar(name.to_string()),
+                Some(("file", path)) => SecretRef::File(path.to_string()),
+                _ => SecretRef::Inline(s),
+            }),
         });
     }
 
-    Ok(parsed_entries)
+    Ok(credentials)
+}
+
+/// Resolves a `SecretRef` into its actual value, reading from the environment or a file as needed.
+fn resolve_secret(secret: &SecretRef) -> Option<String> {
+    match secret {
+        SecretRef::Inline(v) => Some(v.clone()),
+        SecretRef::EnvVar(name) => std::env::var(name).ok(),
+        SecretRef::File(path) => fs::read_to_string(path)
+            .map(|s| s.trim_end_matches('\n').to_string())
+            .ok(),
+    }
+}
+
+/// gio errors worth retrying rather than failing the entry outright: everything that looks like
+/// a transient networking hiccup, as opposed to e.g. an authentication failure.
+fn is_transient(e: &glib::Error) -> bool {
+    e.matches(gio::IOErrorEnum::TimedOut)
+        || e.matches(gio::IOErrorEnum::HostNotFound)
+        || e.matches(gio::IOErrorEnum::ConnectionRefused)
+        // The per-entry `timeout=N` option cancels the mount via `Cancellable::cancel()`, which
+        // surfaces here as `Cancelled` rather than `TimedOut`.
+        || e.matches(gio::IOErrorEnum::Cancelled)
+}
+
+/// Decides whether a failed attempt should be retried given its remaining budget, bumping
+/// `attempts` in place when it is. Returns the new attempt number to use for the backoff delay.
+fn decide_retry(e: &glib::Error, max_retries: u32, attempts: &mut u32) -> Option<u32> {
+    if !is_transient(e) || *attempts >= max_retries {
+        return None;
+    }
+
+    *attempts += 1;
+    Some(*attempts)
+}
+
+/// Drives mounting every entry while keeping at most `max_parallel` attempts in flight at once,
+/// retrying transient failures with exponential backoff. Everything is driven from the single
+/// `MainContext`/`MainLoop` passed in; no OS threads are spawned.
+struct Scheduler {
+    queue: VecDeque<String>,
+    templates: HashMap<String, MountEntry>,
+    max_retries: HashMap<String, u32>,
+    attempts: HashMap<String, u32>,
+    in_flight: usize,
+    max_parallel: usize,
+    g_ctx: glib::MainContext,
+    tx: glib::Sender<Msg>,
+    mounted_files: Arc<Mutex<HashMap<String, gio::File>>>,
+    credentials: Arc<Vec<Credential>>,
+}
+
+impl Scheduler {
+    fn new(
+        entries: Vec<MountEntry>,
+        default_max_retries: u32,
+        max_parallel: usize,
+        g_ctx: glib::MainContext,
+        tx: glib::Sender<Msg>,
+        mounted_files: Arc<Mutex<HashMap<String, gio::File>>>,
+        credentials: Arc<Vec<Credential>>,
+    ) -> Rc<RefCell<Scheduler>> {
+        let mut queue = VecDeque::with_capacity(entries.len());
+        let mut templates = HashMap::with_capacity(entries.len());
+        let mut max_retries = HashMap::with_capacity(entries.len());
+
+        for entry in entries {
+            let uri = entry.mount_path.clone();
+            // A non-zero `retries=N` mount option overrides the binary-wide default for that
+            // entry; `retries=0` or no `retries` option keeps the default.
+            max_retries.insert(
+                uri.clone(),
+                if entry.options.retries > 0 {
+                    entry.options.retries
+                } else {
+                    default_max_retries
+                },
+            );
+            queue.push_back(uri.clone());
+            templates.insert(uri, entry);
+        }
+
+        Rc::new(RefCell::new(Scheduler {
+            queue,
+            templates,
+            max_retries,
+            attempts: HashMap::new(),
+            in_flight: 0,
+            max_parallel,
+            g_ctx,
+            tx,
+            mounted_files,
+            credentials,
+        }))
+    }
+
+    /// Starts as many queued entries as `max_parallel` allows.
+    fn fill(scheduler: &Rc<RefCell<Scheduler>>) {
+        loop {
+            let next_uri = {
+                let mut s = scheduler.borrow_mut();
+                if s.in_flight >= s.max_parallel {
+                    None
+                } else {
+                    s.queue.pop_front()
+                }
+            };
+
+            let uri = match next_uri {
+                Some(uri) => uri,
+                None => break,
+            };
+
+            let entry = scheduler.borrow().templates[&uri].clone();
+            Scheduler::attempt(scheduler, entry);
+        }
+    }
+
+    /// Starts a single mount attempt for `entry`, routed through a private channel so the
+    /// scheduler can see the outcome before deciding whether to retry or report it.
+    fn attempt(scheduler: &Rc<RefCell<Scheduler>>, entry: MountEntry) {
+        let (attempt_tx, attempt_rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+
+        let (g_ctx, mounted_files, credentials) = {
+            let mut s = scheduler.borrow_mut();
+            s.in_flight += 1;
+            (
+                s.g_ctx.clone(),
+                Arc::clone(&s.mounted_files),
+                Arc::clone(&s.credentials),
+            )
+        };
+
+        let scheduler_clone = Rc::clone(scheduler);
+        attempt_rx.attach(Some(&g_ctx), move |msg| {
+            Scheduler::handle_result(&scheduler_clone, msg);
+            glib::Continue(false)
+        });
+
+        handle_mount(entry, attempt_tx, mounted_files, credentials);
+    }
+
+    /// Handles the outcome of a single attempt: retries transient failures with exponential
+    /// backoff, or forwards the final `Msg` to the outer channel and keeps the queue moving.
+    fn handle_result(scheduler: &Rc<RefCell<Scheduler>>, msg: Msg) {
+        let retry = {
+            let mut s = scheduler.borrow_mut();
+            s.in_flight -= 1;
+
+            match &msg.status {
+                MountStatus::Done => {
+                    // A successful mount earns the entry a fresh retry budget for the next
+                    // disconnect cycle, instead of carrying exhausted attempts forever.
+                    s.attempts.remove(&msg.path);
+                    None
+                }
+                MountStatus::Error(e) => {
+                    let max_retries = *s.max_retries.get(&msg.path).unwrap_or(&0);
+                    let attempts = s.attempts.entry(msg.path.clone()).or_insert(0);
+                    decide_retry(e, max_retries, attempts)
+                }
+                _ => None,
+            }
+        };
+
+        match retry {
+            Some(attempt_no) => {
+                let backoff_secs = 2u32.saturating_pow(attempt_no);
+                warn!(
+                    "Retrying {} in {}s (attempt {})",
+                    msg.path, backoff_secs, attempt_no
+                );
+
+                let scheduler_clone = Rc::clone(scheduler);
+                let uri = msg.path.clone();
+                // `Scheduler` is `Rc<RefCell<_>>`, so this must stay on the thread-default
+                // context rather than the `Send`-bound global one.
+                glib::timeout_add_seconds_local(backoff_secs, move || {
+                    scheduler_clone.borrow_mut().queue.push_back(uri.clone());
+                    Scheduler::fill(&scheduler_clone);
+                    glib::Continue(false)
+                });
+            }
+            None => {
+                let tx = scheduler.borrow().tx.clone();
+                if let Err(e) = tx.send(msg) {
+                    error!("Failed to send message in the channel: {}", e);
+                }
+                Scheduler::fill(scheduler);
+            }
+        }
+    }
+}
+
+/// Watches for `mount-removed` events on any of `uris` and re-queues it on `scheduler`, as
+/// gvfs-mount's `mount_monitor` does. Rapid removal/add churn (e.g. around suspend/resume) is
+/// debounced with a short glib timeout before a re-mount is actually attempted.
+fn install_monitor(
+    scheduler: Rc<RefCell<Scheduler>>,
+    uris: HashSet<String>,
+    mounted_files: Arc<Mutex<HashMap<String, gio::File>>>,
+) -> gio::VolumeMonitor {
+    let volume_monitor = gio::VolumeMonitor::get();
+    let debounced: Rc<RefCell<HashMap<String, glib::SourceId>>> = Rc::new(RefCell::new(HashMap::new()));
+
+    volume_monitor.connect_mount_removed(move |_monitor, mount| {
+        let uri = mount.root().uri().to_string();
+        if !uris.contains(&uri) {
+            return;
+        }
+
+        // The mount is gone regardless of whether it gets re-mounted, so its stale handle is
+        // dropped immediately rather than waiting out the re-mount debounce window.
+        mounted_files.lock().unwrap().remove(&uri);
+
+        // A new removal for the same URI restarts the debounce window instead of stacking up.
+        if let Some(source_id) = debounced.borrow_mut().remove(&uri) {
+            source_id.remove();
+        }
+
+        let scheduler = Rc::clone(&scheduler);
+        let debounced_clone = Rc::clone(&debounced);
+        let uri_for_timeout = uri.clone();
+        // `Scheduler` and the debounce map are both `Rc<RefCell<_>>`, so this must stay on the
+        // thread-default context rather than the `Send`-bound global one.
+        let source_id = glib::timeout_add_seconds_local(2, move || {
+            debounced_clone.borrow_mut().remove(&uri_for_timeout);
+            debug!("{} disappeared, re-mounting it", uri_for_timeout);
+            {
+                // A disconnect is logically a new attempt, so it gets its own retry budget
+                // rather than inheriting whatever was left over from the initial mount pass.
+                let mut s = scheduler.borrow_mut();
+                s.attempts.remove(&uri_for_timeout);
+                s.queue.push_back(uri_for_timeout.clone());
+            }
+            Scheduler::fill(&scheduler);
+            glib::Continue(false)
+        });
+        debounced.borrow_mut().insert(uri, source_id);
+    });
+
+    volume_monitor
 }
 
 /// Handles the mount operation to mount the specified entry.
-fn handle_mount(entry: MountEntry, tx: glib::Sender<Msg>) {
+fn handle_mount(
+    entry: MountEntry,
+    tx: glib::Sender<Msg>,
+    mounted_files: Arc<Mutex<HashMap<String, gio::File>>>,
+    credentials: Arc<Vec<Credential>>,
+) {
     debug!("Mounting entry {}", entry.mount_path);
 
     let f = gio::File::for_uri(&entry.mount_path);
+    let f_clone = f.clone();
 
     let mount_op = gio::MountOperation::new();
 
-    if entry.is_anonymous {
+    // `skip-anonymous` forces a credentialed mount even for an `[anonymous]` entry.
+    let is_anonymous = entry.is_anonymous && !entry.options.skip_anonymous;
+    if is_anonymous {
         debug!("Anonymous mount requested for {}", entry.mount_path);
         mount_op.set_anonymous(true);
     }
 
-    mount_op.connect_ask_password(ask_password_cb);
+    // GIO's MountMountFlags has no read-only bit to set directly; the flag is instead carried
+    // through to the Msg/MountReportEntry so it's at least visible in the mount report.
+    if entry.options.readonly {
+        debug!("{} is marked read-only", entry.mount_path);
+    }
+
+    let uri = entry.mount_path.clone();
+    mount_op.connect_ask_password(move |op, message, default_user, default_domain, flags| {
+        ask_password_cb(op, message, default_user, default_domain, flags, &uri, &credentials);
+    });
+
+    let cancellable = gio::Cancellable::new();
+
+    // A per-mount timeout cancels the operation if it hasn't completed in time. The timeout
+    // source is cleared once the mount finishes so it doesn't fire after the fact.
+    let timeout_source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    if let Some(secs) = entry.options.timeout_secs {
+        let cancellable_clone = cancellable.clone();
+        let uri_for_timeout = entry.mount_path.clone();
+        let timeout_source_clone = Rc::clone(&timeout_source);
+        *timeout_source.borrow_mut() = Some(glib::timeout_add_seconds_local(secs, move || {
+            // The source is destroyed once it fires, so the mount callback must not remove it again.
+            timeout_source_clone.borrow_mut().take();
+            warn!("Mount of {} timed out after {}s", uri_for_timeout, secs);
+            cancellable_clone.cancel();
+            glib::Continue(false)
+        }));
+    }
 
     // Callback invoked by gio after setting up the mount.
     let mount_handled_cb = move |r: Result<(), glib::Error>| {
+        if let Some(source_id) = timeout_source.borrow_mut().take() {
+            source_id.remove();
+        }
+
+        let readonly = entry.options.readonly;
         let msg = match r {
-            Ok(_) => Msg {
-                path: entry.mount_path,
-                status: MountStatus::Done,
-            },
+            Ok(_) => {
+                mounted_files
+                    .lock()
+                    .unwrap()
+                    .insert(entry.mount_path.clone(), f_clone);
+                Msg {
+                    path: entry.mount_path,
+                    status: MountStatus::Done,
+                    readonly,
+                }
+            }
             Err(e) => Msg {
                 path: entry.mount_path,
                 status: MountStatus::Error(e),
+                readonly,
             },
         };
         match tx.send(msg) {
@@ -195,11 +821,56 @@ fn handle_mount(entry: MountEntry, tx: glib::Sender<Msg>) {
     f.mount_enclosing_volume(
         gio::MountMountFlags::NONE,
         Some(&mount_op),
-        gio::Cancellable::NONE,
+        Some(&cancellable),
         mount_handled_cb,
     );
 }
 
+/// Handles unmounting or ejecting the specified entry, depending on `action`.
+fn handle_unmount(action: Action, entry: MountEntry, tx: glib::Sender<Msg>) {
+    debug!("{:?}ing entry {}", action, entry.mount_path);
+
+    let f = gio::File::for_uri(&entry.mount_path);
+
+    // Callback invoked by gio after the teardown operation completes.
+    let op_handled_cb = move |r: Result<(), glib::Error>| {
+        let readonly = entry.options.readonly;
+        let msg = match r {
+            Ok(_) => Msg {
+                path: entry.mount_path,
+                status: MountStatus::Done,
+                readonly,
+            },
+            Err(e) => Msg {
+                path: entry.mount_path,
+                status: MountStatus::Error(e),
+                readonly,
+            },
+        };
+        match tx.send(msg) {
+            Ok(_) => {}
+            Err(e) => error!("Failed to send message in the channel: {}", e),
+        };
+        drop(tx);
+    };
+
+    match action {
+        Action::Unmount => f.unmount_mountable_with_operation(
+            gio::MountUnmountFlags::NONE,
+            gio::MountOperation::NONE,
+            gio::Cancellable::NONE,
+            op_handled_cb,
+        ),
+        Action::Eject => f.eject_mountable_with_operation(
+            gio::MountUnmountFlags::NONE,
+            gio::MountOperation::NONE,
+            gio::Cancellable::NONE,
+            op_handled_cb,
+        ),
+        Action::Mount => unreachable!("handle_unmount is never called with Action::Mount"),
+    }
+}
+
 /// Callback that is invoked by gio when prompted for password.
 fn ask_password_cb(
     mount_op: &gio::MountOperation,
@@ -207,7 +878,48 @@ fn ask_password_cb(
     _: &str,
     _: &str,
     flags: gio::AskPasswordFlags,
+    uri: &str,
+    credentials: &[Credential],
 ) {
+    if let Some(cred) = credentials.iter().find(|c| uri.starts_with(c.uri_prefix.as_str())) {
+        unsafe {
+            if let Some(data) = mount_op.data("state") {
+                // Ensures that a wrong credential doesn't loop forever.
+                if let MountStatus::Asked = *(data.as_ptr()) {
+                    warn!("Configured credentials for {} were rejected.", uri);
+                    mount_op.reply(gio::MountOperationResult::Aborted);
+                    return;
+                }
+            }
+            mount_op.set_data("state", MountStatus::Asked);
+        }
+
+        if flags.contains(gio::AskPasswordFlags::NEEDS_USERNAME) {
+            if let Some(username) = &cred.username {
+                mount_op.set_username(username);
+            }
+        }
+        if flags.contains(gio::AskPasswordFlags::NEEDS_DOMAIN) {
+            if let Some(domain) = &cred.domain {
+                mount_op.set_domain(domain);
+            }
+        }
+        if flags.contains(gio::AskPasswordFlags::NEEDS_PASSWORD) {
+            match cred.password.as_ref().and_then(resolve_secret) {
+                Some(password) => mount_op.set_password(&password),
+                None => {
+                    warn!("Could not resolve a password for {}", uri);
+                    mount_op.reply(gio::MountOperationResult::Aborted);
+                    return;
+                }
+            }
+        }
+
+        debug!("Using configured credentials for {}", uri);
+        mount_op.reply(gio::MountOperationResult::Handled);
+        return;
+    }
+
     if mount_op.is_anonymous() && flags.contains(gio::AskPasswordFlags::ANONYMOUS_SUPPORTED) {
         unsafe {
             if let Some(data) = mount_op.data("state") {
@@ -232,4 +944,4 @@ fn ask_password_cb(
     }
 }
 
-mod test;
\ No newline at end of file
+mod test;
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("adsys_mount_test_{}_{}", std::process::id(), name));
+        let mut f = fs::File::create(&path).unwrap();
+        f.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_credentials_resolves_inline_env_and_file_secrets() {
+        std::env::set_var("ADSYS_MOUNT_TEST_PASSWORD", "from-env");
+        let password_file = write_temp_file("password", "from-file\n");
+
+        let content = format!(
+            "smb://inline/share alice WORKGROUP secret\nsmb://env/share bob - env:ADSYS_MOUNT_TEST_PASSWORD\nsmb://file/share - - file:{}\n",
+            password_file.display()
+        );
+        let creds_file = write_temp_file("credentials", &content);
+
+        let credentials = parse_credentials(&creds_file.display().to_string()).unwrap();
+        assert_eq!(credentials.len(), 3);
+
+        assert_eq!(credentials[0].uri_prefix, "smb://inline/share");
+        assert_eq!(credentials[0].username.as_deref(), Some("alice"));
+        assert_eq!(credentials[0].domain.as_deref(), Some("WORKGROUP"));
+        assert_eq!(
+            resolve_secret(credentials[0].password.as_ref().unwrap()),
+            Some("secret".to_string())
+        );
+
+        assert_eq!(credentials[1].domain, None);
+        assert_eq!(
+            resolve_secret(credentials[1].password.as_ref().unwrap()),
+            Some("from-env".to_string())
+        );
+
+        assert_eq!(credentials[2].username, None);
+        assert_eq!(
+            resolve_secret(credentials[2].password.as_ref().unwrap()),
+            Some("from-file".to_string())
+        );
+
+        fs::remove_file(&password_file).unwrap();
+        fs::remove_file(&creds_file).unwrap();
+        std::env::remove_var("ADSYS_MOUNT_TEST_PASSWORD");
+    }
+
+    #[test]
+    fn parse_credentials_ignores_malformed_lines() {
+        let creds_file = write_temp_file("malformed_credentials", "smb://host/share alice\n");
+
+        let credentials = parse_credentials(&creds_file.display().to_string()).unwrap();
+        assert!(credentials.is_empty());
+
+        fs::remove_file(&creds_file).unwrap();
+    }
+
+    #[test]
+    fn resolve_secret_missing_env_var_is_none() {
+        std::env::remove_var("ADSYS_MOUNT_TEST_MISSING");
+        let secret = SecretRef::EnvVar("ADSYS_MOUNT_TEST_MISSING".to_string());
+        assert_eq!(resolve_secret(&secret), None);
+    }
+
+    #[test]
+    fn parse_mount_options_known_and_unknown_keys() {
+        let options = parse_mount_options("ro,timeout=30,retries=3,bogus,skip-anonymous");
+        assert!(options.readonly);
+        assert_eq!(options.timeout_secs, Some(30));
+        assert_eq!(options.retries, 3);
+        assert!(options.skip_anonymous);
+    }
+
+    #[test]
+    fn parse_mount_options_invalid_numeric_values_are_ignored() {
+        let options = parse_mount_options("timeout=notanumber,retries=oops");
+        assert_eq!(options.timeout_secs, None);
+        assert_eq!(options.retries, 0);
+    }
+
+    #[test]
+    fn parse_mount_options_empty_string_is_default() {
+        assert_eq!(parse_mount_options(""), MountOptions::default());
+    }
+
+    #[test]
+    fn is_transient_matches_timeouts_and_network_errors() {
+        assert!(is_transient(&glib::Error::new(gio::IOErrorEnum::TimedOut, "timed out")));
+        assert!(is_transient(&glib::Error::new(
+            gio::IOErrorEnum::HostNotFound,
+            "host not found"
+        )));
+        assert!(is_transient(&glib::Error::new(
+            gio::IOErrorEnum::ConnectionRefused,
+            "connection refused"
+        )));
+        // A per-entry `timeout=N` cancels the mount, which gio reports as Cancelled rather than
+        // TimedOut.
+        assert!(is_transient(&glib::Error::new(
+            gio::IOErrorEnum::Cancelled,
+            "cancelled"
+        )));
+    }
+
+    #[test]
+    fn is_transient_does_not_match_auth_or_already_mounted_errors() {
+        assert!(!is_transient(&glib::Error::new(
+            gio::IOErrorEnum::PermissionDenied,
+            "denied"
+        )));
+        assert!(!is_transient(&glib::Error::new(
+            gio::IOErrorEnum::AlreadyMounted,
+            "already mounted"
+        )));
+    }
+
+    #[test]
+    fn decide_retry_stops_once_the_budget_is_exhausted() {
+        let mut attempts = 0;
+        let e = glib::Error::new(gio::IOErrorEnum::TimedOut, "timed out");
+
+        assert_eq!(decide_retry(&e, 2, &mut attempts), Some(1));
+        assert_eq!(decide_retry(&e, 2, &mut attempts), Some(2));
+        assert_eq!(decide_retry(&e, 2, &mut attempts), None);
+    }
+
+    #[test]
+    fn decide_retry_never_retries_non_transient_errors() {
+        let mut attempts = 0;
+        let e = glib::Error::new(gio::IOErrorEnum::PermissionDenied, "denied");
+
+        assert_eq!(decide_retry(&e, 5, &mut attempts), None);
+        assert_eq!(attempts, 0);
+    }
+
+    #[test]
+    fn monitor_reconnect_gets_a_fresh_retry_budget() {
+        let uri = "smb://flaky/share".to_string();
+        let entry = MountEntry {
+            mount_path: uri.clone(),
+            is_anonymous: false,
+            options: parse_mount_options("retries=1"),
+        };
+
+        let scheduler = Scheduler::new(
+            vec![entry],
+            0,
+            4,
+            glib::MainContext::default(),
+            glib::MainContext::channel(glib::PRIORITY_DEFAULT).0,
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::new(Vec::new()),
+        );
+
+        let e = glib::Error::new(gio::IOErrorEnum::TimedOut, "timed out");
+
+        // Exhausts the retry budget, as repeated transient failures during the initial mount
+        // pass would.
+        {
+            let mut s = scheduler.borrow_mut();
+            let max_retries = *s.max_retries.get(&uri).unwrap();
+            let attempts = s.attempts.entry(uri.clone()).or_insert(0);
+            assert_eq!(decide_retry(&e, max_retries, attempts), Some(1));
+            assert_eq!(decide_retry(&e, max_retries, attempts), None);
+        }
+
+        // A disconnect under --monitor resets the budget, the same way install_monitor's
+        // re-queue does, instead of permanently inheriting the exhausted counter.
+        scheduler.borrow_mut().attempts.remove(&uri);
+
+        let mut s = scheduler.borrow_mut();
+        let max_retries = *s.max_retries.get(&uri).unwrap();
+        let attempts = s.attempts.entry(uri.clone()).or_insert(0);
+        assert_eq!(decide_retry(&e, max_retries, attempts), Some(1));
+    }
+
+    #[test]
+    fn mount_report_entry_from_msg_maps_each_status() {
+        let done = Msg {
+            path: "smb://host/done".to_string(),
+            status: MountStatus::Done,
+            readonly: true,
+        };
+        let report = MountReportEntry::from_msg(&done);
+        assert_eq!(report.uri, "smb://host/done");
+        assert_eq!(report.status, "done");
+        assert!(report.readonly);
+        assert!(report.error.is_none());
+
+        let already_mounted = Msg {
+            path: "smb://host/already".to_string(),
+            status: MountStatus::Error(glib::Error::new(
+                gio::IOErrorEnum::AlreadyMounted,
+                "already mounted",
+            )),
+            readonly: false,
+        };
+        let report = MountReportEntry::from_msg(&already_mounted);
+        assert_eq!(report.status, "already-mounted");
+        assert!(report.error.is_none());
+
+        let not_mounted = Msg {
+            path: "smb://host/gone".to_string(),
+            status: MountStatus::Error(glib::Error::new(gio::IOErrorEnum::NotMounted, "not mounted")),
+            readonly: false,
+        };
+        let report = MountReportEntry::from_msg(&not_mounted);
+        assert_eq!(report.status, "not-mounted");
+        assert!(report.error.is_none());
+
+        let failed = Msg {
+            path: "smb://host/failed".to_string(),
+            status: MountStatus::Error(glib::Error::new(gio::IOErrorEnum::PermissionDenied, "denied")),
+            readonly: false,
+        };
+        let report = MountReportEntry::from_msg(&failed);
+        assert_eq!(report.status, "error");
+        let error = report.error.unwrap();
+        assert_eq!(
+            error.code,
+            glib::translate::IntoGlib::into_glib(gio::IOErrorEnum::PermissionDenied)
+        );
+        assert_eq!(error.message, "denied");
+
+        let asked = Msg {
+            path: "smb://host/asked".to_string(),
+            status: MountStatus::Asked,
+            readonly: false,
+        };
+        let report = MountReportEntry::from_msg(&asked);
+        assert_eq!(report.status, "asked");
+        assert!(report.error.is_none());
+    }
+}